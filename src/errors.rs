@@ -0,0 +1,96 @@
+use std::error;
+use std::fmt;
+use std::string;
+
+use rustc_serialize::base64;
+use rustc_serialize::json;
+
+#[derive(Debug)]
+/// All the errors we can run into while creating/parsing JWTs
+pub enum Error {
+    /// The token doesn't have a valid JWT shape (header.claims.signature)
+    InvalidToken,
+    /// The signature doesn't match
+    InvalidSignature,
+    /// The `alg` in the header isn't one of the algorithms you're decoding with
+    WrongAlgorithmHeader,
+    /// The signing/verification key couldn't be parsed or used
+    InvalidKey,
+    /// The token's `exp` claim says it has expired
+    ExpiredSignature,
+    /// The token's `nbf` claim says it isn't valid yet
+    ImmatureSignature,
+    /// The token's `iat` claim is in the future
+    IssuedInFuture,
+    /// The token's `iss` claim doesn't match the expected issuer
+    InvalidIssuer,
+    /// The token's `sub` claim doesn't match the expected subject
+    InvalidSubject,
+    /// The token's `aud` claim doesn't match the expected audience
+    InvalidAudience,
+    Utf8(string::FromUtf8Error),
+    Base64(base64::FromBase64Error),
+    JsonEncode(json::EncoderError),
+    JsonDecode(json::DecoderError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", error::Error::description(self))
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::InvalidToken => "invalid token",
+            Error::InvalidSignature => "invalid signature",
+            Error::WrongAlgorithmHeader => "wrong algorithm header",
+            Error::InvalidKey => "invalid key",
+            Error::ExpiredSignature => "expired signature",
+            Error::ImmatureSignature => "immature signature",
+            Error::IssuedInFuture => "token issued in the future",
+            Error::InvalidIssuer => "invalid issuer",
+            Error::InvalidSubject => "invalid subject",
+            Error::InvalidAudience => "invalid audience",
+            Error::Utf8(ref err) => err.description(),
+            Error::Base64(ref err) => err.description(),
+            Error::JsonEncode(ref err) => err.description(),
+            Error::JsonDecode(ref err) => err.description(),
+        }
+    }
+
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            Error::Utf8(ref err) => Some(err),
+            Error::Base64(ref err) => Some(err),
+            Error::JsonEncode(ref err) => Some(err),
+            Error::JsonDecode(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<string::FromUtf8Error> for Error {
+    fn from(err: string::FromUtf8Error) -> Error {
+        Error::Utf8(err)
+    }
+}
+
+impl From<base64::FromBase64Error> for Error {
+    fn from(err: base64::FromBase64Error) -> Error {
+        Error::Base64(err)
+    }
+}
+
+impl From<json::EncoderError> for Error {
+    fn from(err: json::EncoderError) -> Error {
+        Error::JsonEncode(err)
+    }
+}
+
+impl From<json::DecoderError> for Error {
+    fn from(err: json::DecoderError) -> Error {
+        Error::JsonDecode(err)
+    }
+}
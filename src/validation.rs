@@ -0,0 +1,131 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rustc_serialize::json::Json;
+
+use errors::Error;
+use Algorithm;
+
+/// Contains the various validations that are applied after the token's signature has
+/// been checked. Construct one with `Validation::default()` and tweak the fields you
+/// care about; everything else keeps a sane default.
+#[derive(Debug, Clone)]
+pub struct Validation {
+    /// Number of seconds of clock skew to tolerate when checking `exp`, `nbf` and `iat`
+    pub leeway: i64,
+    /// Whether to validate the `exp` claim, defaults to `true`
+    pub validate_exp: bool,
+    /// Whether to validate the `nbf` claim, defaults to `false`
+    pub validate_nbf: bool,
+    /// Whether to validate the `iat` claim, defaults to `false`. When enabled, rejects
+    /// tokens whose `iat` is in the future (beyond `leeway`)
+    pub validate_iat: bool,
+    /// If set, the token's `iss` claim must match this value
+    pub iss: Option<String>,
+    /// If set, the token's `sub` claim must match this value
+    pub sub: Option<String>,
+    /// If set, the token's `aud` claim must match this value
+    pub aud: Option<String>,
+    /// The set of algorithms `decode` accepts. The header's `alg` is checked against
+    /// this allowlist *before* any cryptographic work is done, and the algorithm used
+    /// to verify the signature is always the one named in the header, never a fallback
+    /// chosen outside this set. Defaults to `[Algorithm::HS256]`
+    pub algorithms: Vec<Algorithm>,
+}
+
+impl Default for Validation {
+    fn default() -> Validation {
+        Validation {
+            leeway: 0,
+            validate_exp: true,
+            validate_nbf: false,
+            validate_iat: false,
+            iss: None,
+            sub: None,
+            aud: None,
+            algorithms: vec![Algorithm::HS256],
+        }
+    }
+}
+
+fn now() -> i64 {
+    let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).expect("system time before 1970");
+    since_epoch.as_secs() as i64
+}
+
+/// Looks up a NumericDate claim (`exp`/`nbf`/`iat`). Per RFC 7519 `NumericDate` is a
+/// JSON numeric value, which some producers serialize as a float (e.g. `1600000000.0`)
+/// rather than an integer, so both representations are accepted.
+fn find_i64(claims: &Json, key: &str) -> Option<i64> {
+    claims.find(key).and_then(|value| {
+        value.as_i64().or_else(|| value.as_f64().map(|f| f as i64))
+    })
+}
+
+fn find_string<'a>(claims: &'a Json, key: &str) -> Option<&'a str> {
+    claims.find(key).and_then(|value| value.as_string())
+}
+
+/// Checks whether `expected` is present in the `aud` claim, which per RFC 7519 may be
+/// either a single string or an array of strings
+fn matches_aud(claims: &Json, expected: &str) -> bool {
+    match claims.find("aud") {
+        Some(&Json::String(ref aud)) => aud == expected,
+        Some(&Json::Array(ref auds)) => {
+            auds.iter().any(|aud| aud.as_string() == Some(expected))
+        }
+        _ => false,
+    }
+}
+
+impl Validation {
+    /// Runs the configured checks against the raw (still encoded as a JSON object)
+    /// claims of a token. Called by `decode` after the signature has been verified.
+    pub fn validate(&self, claims_json: &str) -> Result<(), Error> {
+        let claims = try!(Json::from_str(claims_json).map_err(|_| Error::InvalidToken));
+        let now = now();
+
+        if self.validate_exp {
+            if let Some(exp) = find_i64(&claims, "exp") {
+                if now - self.leeway > exp {
+                    return Err(Error::ExpiredSignature);
+                }
+            }
+        }
+
+        if self.validate_nbf {
+            if let Some(nbf) = find_i64(&claims, "nbf") {
+                if nbf - self.leeway > now {
+                    return Err(Error::ImmatureSignature);
+                }
+            }
+        }
+
+        if self.validate_iat {
+            if let Some(iat) = find_i64(&claims, "iat") {
+                if iat - self.leeway > now {
+                    return Err(Error::IssuedInFuture);
+                }
+            }
+        }
+
+        if let Some(ref iss) = self.iss {
+            if find_string(&claims, "iss") != Some(iss.as_str()) {
+                return Err(Error::InvalidIssuer);
+            }
+        }
+
+        if let Some(ref sub) = self.sub {
+            if find_string(&claims, "sub") != Some(sub.as_str()) {
+                return Err(Error::InvalidSubject);
+            }
+        }
+
+        if let Some(ref aud) = self.aud {
+            if !matches_aud(&claims, aud) {
+                return Err(Error::InvalidAudience);
+            }
+        }
+
+        Ok(())
+    }
+}